@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::utils::is_important;
 
@@ -40,14 +40,60 @@ pub enum ServerMessage {
     SetReply(SetReply),
 }
 
-#[derive(Clone, Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u16)]
+#[derive(Debug, Clone, Copy)]
 pub enum Permission {
-    Disabled = 0,
-    Enabled = 1,
-    Goal = 2,
-    Auto = 6,
-    AutoEnabled = 7,
+    Disabled,
+    Enabled,
+    Goal,
+    Auto,
+    AutoEnabled,
+    /// An unrecognized permission code, round-tripped rather than rejected so a
+    /// server ahead of this crate's protocol version doesn't fail parsing.
+    Unknown(u16),
+}
+
+impl From<u16> for Permission {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Permission::Disabled,
+            1 => Permission::Enabled,
+            2 => Permission::Goal,
+            6 => Permission::Auto,
+            7 => Permission::AutoEnabled,
+            other => Permission::Unknown(other),
+        }
+    }
+}
+
+impl From<Permission> for u16 {
+    fn from(value: Permission) -> Self {
+        match value {
+            Permission::Disabled => 0,
+            Permission::Enabled => 1,
+            Permission::Goal => 2,
+            Permission::Auto => 6,
+            Permission::AutoEnabled => 7,
+            Permission::Unknown(value) => value,
+        }
+    }
+}
+
+impl Serialize for Permission {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        u16::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Permission {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Permission::from(u16::deserialize(deserializer)?))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -74,12 +120,53 @@ pub struct NetworkItem {
     pub flags: i32,
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u16)]
+#[derive(Debug, Clone, Copy)]
 pub enum SlotType {
-    Spectator = 0,
-    Player = 1,
-    Group = 2,
+    Spectator,
+    Player,
+    Group,
+    /// An unrecognized slot type code, kept around instead of failing the parse.
+    Unknown(u16),
+}
+
+impl From<u16> for SlotType {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => SlotType::Spectator,
+            1 => SlotType::Player,
+            2 => SlotType::Group,
+            other => SlotType::Unknown(other),
+        }
+    }
+}
+
+impl From<SlotType> for u16 {
+    fn from(value: SlotType) -> Self {
+        match value {
+            SlotType::Spectator => 0,
+            SlotType::Player => 1,
+            SlotType::Group => 2,
+            SlotType::Unknown(value) => value,
+        }
+    }
+}
+
+impl Serialize for SlotType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        u16::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SlotType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(SlotType::from(u16::deserialize(deserializer)?))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -135,14 +222,16 @@ pub struct StatusUpdate {
     pub status: ClientStatus,
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u16)]
+#[derive(Debug, Clone, Copy)]
 pub enum ClientStatus {
-    ClientUnknown = 0,
-    ClientConnected = 5,
-    ClientReady = 10,
-    ClientPlaying = 20,
-    ClientGoal = 30,
+    ClientUnknown,
+    ClientConnected,
+    ClientReady,
+    ClientPlaying,
+    ClientGoal,
+    /// An unrecognized client status code; `network_version()` is pinned to 0.5.0,
+    /// so this is how a newer server's status codes survive parsing.
+    Unknown(u16),
 }
 
 impl From<u16> for ClientStatus {
@@ -153,11 +242,42 @@ impl From<u16> for ClientStatus {
             10 => ClientStatus::ClientReady,
             20 => ClientStatus::ClientPlaying,
             30 => ClientStatus::ClientGoal,
-            _ => panic!("Bad value provided for ClientStatus ({value})"),
+            other => ClientStatus::Unknown(other),
+        }
+    }
+}
+
+impl From<ClientStatus> for u16 {
+    fn from(value: ClientStatus) -> Self {
+        match value {
+            ClientStatus::ClientUnknown => 0,
+            ClientStatus::ClientConnected => 5,
+            ClientStatus::ClientReady => 10,
+            ClientStatus::ClientPlaying => 20,
+            ClientStatus::ClientGoal => 30,
+            ClientStatus::Unknown(value) => value,
         }
     }
 }
 
+impl Serialize for ClientStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        u16::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ClientStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(ClientStatus::from(u16::deserialize(deserializer)?))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Say {
     pub text: String,
@@ -177,6 +297,36 @@ pub struct Bounce {
     pub data: Value,
 }
 
+/// The `tags` value that marks a `Bounce`/`Bounced` packet as carrying a [`DeathLink`].
+pub const DEATH_LINK_TAG: &str = "DeathLink";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeathLink {
+    pub time: f64,
+    pub cause: Option<String>,
+    pub source: String,
+}
+
+impl DeathLink {
+    pub fn new(source: impl Into<String>, cause: Option<String>, time: f64) -> Self {
+        Self {
+            time,
+            cause,
+            source: source.into(),
+        }
+    }
+
+    /// Wraps this death link into a `Bounce` tagged `"DeathLink"`, ready to send to the server.
+    pub fn into_bounce(self) -> Bounce {
+        Bounce {
+            games: None,
+            slots: None,
+            tags: Some(vec![DEATH_LINK_TAG.to_string()]),
+            data: serde_json::to_value(self).expect("DeathLink always serializes"),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Get {
     pub keys: Vec<String>,
@@ -191,9 +341,26 @@ pub struct Set {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct DataStorageOperation {
-    pub replace: String, // TODO: enum-ify?
-    pub value: Value,
+#[serde(tag = "operation", content = "value", rename_all = "snake_case")]
+pub enum DataStorageOperation {
+    Replace(Value),
+    Default(Value),
+    Add(Value),
+    Mul(Value),
+    Pow(Value),
+    Mod(Value),
+    Floor(Value),
+    Ceil(Value),
+    Max(Value),
+    Min(Value),
+    And(Value),
+    Or(Value),
+    Xor(Value),
+    LeftShift(Value),
+    RightShift(Value),
+    Remove(Value),
+    Pop(Value),
+    Update(Value),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -280,13 +447,140 @@ pub struct Print {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PrintJSON {
     pub data: Vec<JSONMessagePart>,
-    pub r#type: Option<String>,
+    pub r#type: Option<PrintJsonType>,
     pub receiving: Option<i32>,
     pub item: Option<NetworkItem>,
     pub found: Option<bool>,
     pub countdown: Option<i32>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PrintJsonType {
+    ItemSend,
+    ItemCheat,
+    Hint,
+    Join,
+    Part,
+    Chat,
+    ServerChat,
+    Tutorial,
+    TagsChanged,
+    CommandResult,
+    AdminCommandResult,
+    Goal,
+    Release,
+    Collect,
+    Countdown,
+}
+
+/// Context needed to resolve the numeric ids embedded in a [`JSONMessagePart`]
+/// into human-readable names when rendering a [`PrintJSON`] packet.
+pub struct PrintJsonContext<'a> {
+    /// The viewing client's own team, used together with the slot number in a
+    /// `player_id` part's `text` to disambiguate `NetworkPlayer` entries that
+    /// reuse slot numbers across teams.
+    pub team: i32,
+    /// Maps a slot to the game it's playing, so each `item_id`/`location_id` part
+    /// can be resolved against the correct game rather than one packet-wide game.
+    pub slot_info: &'a HashMap<String, NetworkSlot>,
+    pub package: &'a DataPackageObject,
+    pub players: &'a [NetworkPlayer],
+}
+
+impl PrintJSON {
+    /// Renders this packet's `data` parts into a single styled line, resolving
+    /// ids into names and `color` tokens into ANSI escape codes.
+    pub fn render(&self, ctx: &PrintJsonContext) -> String {
+        self.data.iter().map(|part| part.render(ctx)).collect()
+    }
+}
+
+impl JSONMessagePart {
+    pub fn render(&self, ctx: &PrintJsonContext) -> String {
+        let text = self.text.as_deref().unwrap_or_default();
+        let game = part_game(self.player, ctx.slot_info);
+
+        let resolved = match self.r#type.as_deref() {
+            Some("player_id") => resolve_player_name(text, ctx.team, ctx.players),
+            Some("item_id") => resolve_data_name(text, ctx.package, game, |game| {
+                &game.item_name_to_id
+            }),
+            Some("location_id") => resolve_data_name(text, ctx.package, game, |game| {
+                &game.location_name_to_id
+            }),
+            _ => text.to_string(),
+        };
+
+        match self.color.as_deref().and_then(ansi_color_code) {
+            Some(code) => format!("\x1b[{code}m{resolved}\x1b[0m"),
+            None => resolved,
+        }
+    }
+}
+
+/// Looks up the game a part's `player` slot is playing, via the room's `slot_info`.
+fn part_game(player: Option<i32>, slot_info: &HashMap<String, NetworkSlot>) -> Option<&str> {
+    let player = player?;
+    slot_info.get(&player.to_string()).map(|slot| slot.game.as_str())
+}
+
+fn resolve_player_name(text: &str, team: i32, players: &[NetworkPlayer]) -> String {
+    let Ok(slot) = text.parse::<i32>() else {
+        return text.to_string();
+    };
+
+    players
+        .iter()
+        .find(|player| player.team == team && player.slot == slot)
+        .map(|player| player.alias.clone())
+        .unwrap_or_else(|| text.to_string())
+}
+
+fn resolve_data_name(
+    text: &str,
+    package: &DataPackageObject,
+    game: Option<&str>,
+    names: impl Fn(&GameData) -> &HashMap<String, i32>,
+) -> String {
+    let Ok(id) = text.parse::<i32>() else {
+        return text.to_string();
+    };
+
+    game.and_then(|game| package.games.get(game))
+        .and_then(|game_data| {
+            names(game_data)
+                .iter()
+                .find(|(_, &value)| value == id)
+                .map(|(name, _)| name.clone())
+        })
+        .unwrap_or_else(|| text.to_string())
+}
+
+/// Maps an Archipelago protocol color token to its ANSI escape code.
+fn ansi_color_code(color: &str) -> Option<&'static str> {
+    Some(match color {
+        "bold" => "1",
+        "underline" => "4",
+        "black" => "30",
+        "red" => "31",
+        "green" => "32",
+        "yellow" => "33",
+        "blue" => "34",
+        "magenta" => "35",
+        "cyan" => "36",
+        "white" => "37",
+        "black_bg" => "40",
+        "red_bg" => "41",
+        "green_bg" => "42",
+        "yellow_bg" => "43",
+        "blue_bg" => "44",
+        "magenta_bg" => "45",
+        "cyan_bg" => "46",
+        "white_bg" => "47",
+        _ => return None,
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct HintData {
     pub receiving: i32,
@@ -363,18 +657,101 @@ pub struct DataPackageObject {
     pub games: HashMap<String, GameData>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameData {
     pub item_name_to_id: HashMap<String, i32>,
     pub location_name_to_id: HashMap<String, i32>,
 }
 
+/// Persists fetched [`GameData`] keyed by game name, alongside the checksum it was fetched with.
+///
+/// Implement this over disk, a database, or (see [`InMemoryDataPackageStore`]) plain memory to
+/// avoid re-fetching the data package for games whose checksum hasn't changed since last connect.
+pub trait DataPackageStore {
+    fn read(&self, game: &str) -> Option<(String, GameData)>;
+    fn write(&mut self, game: &str, checksum: String, data: GameData);
+}
+
+/// Builds a [`GetDataPackage`] request covering only the games in `checksums` whose
+/// cached entry in `store` is missing or out of date.
+pub fn stale_data_package_request(
+    checksums: &HashMap<String, String>,
+    store: &impl DataPackageStore,
+) -> GetDataPackage {
+    let stale_games: Vec<String> = checksums
+        .iter()
+        .filter(|(game, checksum)| {
+            store
+                .read(game)
+                .is_none_or(|(cached_checksum, _)| &cached_checksum != *checksum)
+        })
+        .map(|(game, _)| game.clone())
+        .collect();
+
+    // `games: None` means "send everything" on the wire, not "nothing is stale" —
+    // an empty `Some(vec![])` is how we ask for zero games.
+    GetDataPackage {
+        games: Some(stale_games),
+    }
+}
+
+/// Writes every game in a fetched [`DataPackage`] back into `store`, keyed by the
+/// checksum the room advertised for it. Games with no entry in `checksums` are
+/// not written.
+pub fn update_data_package_store(
+    package: DataPackage,
+    checksums: &HashMap<String, String>,
+    store: &mut impl DataPackageStore,
+) {
+    for (game, data) in package.data.games {
+        if let Some(checksum) = checksums.get(&game) {
+            store.write(&game, checksum.clone(), data);
+        }
+    }
+}
+
+/// A bare in-memory [`DataPackageStore`], useful for tests or short-lived clients
+/// that don't need the cache to survive a restart.
+#[derive(Debug, Default)]
+pub struct InMemoryDataPackageStore {
+    games: HashMap<String, (String, GameData)>,
+}
+
+impl DataPackageStore for InMemoryDataPackageStore {
+    fn read(&self, game: &str) -> Option<(String, GameData)> {
+        self.games.get(game).cloned()
+    }
+
+    fn write(&mut self, game: &str, checksum: String, data: GameData) {
+        self.games.insert(game.to_string(), (checksum, data));
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Bounced {
     pub games: Option<Vec<String>>,
     pub slots: Option<Vec<i32>>,
     pub tags: Option<Vec<String>>,
-    pub data: Bounce,
+    pub data: Value,
+}
+
+impl Bounced {
+    /// Recognizes a `Bounced` tagged `"DeathLink"` and decodes its payload.
+    ///
+    /// Returns `None` if this bounce isn't tagged as a death link, or if its
+    /// data doesn't match the expected shape.
+    pub fn as_death_link(&self) -> Option<DeathLink> {
+        let is_death_link = self
+            .tags
+            .as_ref()
+            .is_some_and(|tags| tags.iter().any(|tag| tag == DEATH_LINK_TAG));
+
+        if !is_death_link {
+            return None;
+        }
+
+        serde_json::from_value(self.data.clone()).ok()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -391,7 +768,338 @@ pub struct Retrieved {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SetReply {
-    key: String,
-    value: Value,
-    original_value: Value,
+    pub key: String,
+    pub value: Value,
+    pub original_value: Value,
+}
+
+/// A reactive view over the server's data storage, tracking the last-known value
+/// of every key this client has seen via `Retrieved` or `SetReply`.
+#[derive(Debug, Default)]
+pub struct DataStorageClient {
+    values: RwLock<HashMap<String, Value>>,
+    subscribed: RwLock<HashSet<String>>,
+}
+
+/// A change to a watched data-storage key, as reported by a `SetReply`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataStorageChange {
+    pub key: String,
+    pub value: Value,
+    pub original_value: Value,
+}
+
+/// The result of [`DataStorageClient::read`]: either an immediate cache hit, or a
+/// request the caller must send and later reconcile with [`DataStorageClient::reconcile_retrieved`].
+#[derive(Debug)]
+pub enum DataStorageRead {
+    Cached(Value),
+    Fetch(Get),
+}
+
+impl DataStorageClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to future changes on the given keys, tracking them on the
+    /// client so the subscription can be rebuilt later via
+    /// [`DataStorageClient::resubscribe`] (e.g. after a reconnect).
+    ///
+    /// This only constructs the request; the caller still needs to send it and
+    /// route the resulting `SetReply`s into [`DataStorageClient::reconcile_set_reply`].
+    pub fn subscribe(&self, keys: Vec<String>) -> SetNotify {
+        self.subscribed
+            .write()
+            .unwrap()
+            .extend(keys.iter().cloned());
+
+        SetNotify { keys }
+    }
+
+    /// Rebuilds a `SetNotify` covering every key subscribed so far.
+    pub fn resubscribe(&self) -> SetNotify {
+        SetNotify {
+            keys: self.subscribed.read().unwrap().iter().cloned().collect(),
+        }
+    }
+
+    /// Returns the set of keys currently subscribed to.
+    pub fn subscribed_keys(&self) -> HashSet<String> {
+        self.subscribed.read().unwrap().clone()
+    }
+
+    /// Returns the cached value for `key`, if this client has seen one, without
+    /// touching the network.
+    pub fn read_cached(&self, key: &str) -> Option<Value> {
+        self.values.read().unwrap().get(key).cloned()
+    }
+
+    /// Answers a read from the cache, falling back to a `Get` request the caller
+    /// should send and later reconcile via [`DataStorageClient::reconcile_retrieved`].
+    pub fn read(&self, key: &str) -> DataStorageRead {
+        match self.read_cached(key) {
+            Some(value) => DataStorageRead::Cached(value),
+            None => DataStorageRead::Fetch(Get {
+                keys: vec![key.to_string()],
+            }),
+        }
+    }
+
+    /// Folds an incoming `Retrieved` packet into the cache.
+    pub fn reconcile_retrieved(&self, retrieved: Retrieved) {
+        self.values.write().unwrap().extend(retrieved.keys);
+    }
+
+    /// Folds an incoming `SetReply` into the cache, returning the resulting change event.
+    pub fn reconcile_set_reply(&self, reply: SetReply) -> DataStorageChange {
+        self.values
+            .write()
+            .unwrap()
+            .insert(reply.key.clone(), reply.value.clone());
+
+        DataStorageChange {
+            key: reply.key,
+            value: reply.value,
+            original_value: reply.original_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn data_storage_operation_round_trips_wire_format() {
+        let op = DataStorageOperation::Add(json!(1));
+        let wire = serde_json::to_value(&op).unwrap();
+        assert_eq!(wire, json!({"operation": "add", "value": 1}));
+        assert!(matches!(
+            serde_json::from_value::<DataStorageOperation>(wire).unwrap(),
+            DataStorageOperation::Add(v) if v == json!(1)
+        ));
+    }
+
+    /// Asserts a repr enum deserializes an unrecognized code into its `Unknown`
+    /// variant and serializes it back out unchanged, for all three forward-compatible
+    /// repr enums ([`Permission`], [`SlotType`], [`ClientStatus`]) at once.
+    macro_rules! unknown_code_round_trip_test {
+        ($name:ident, $ty:ty, $variant:path) => {
+            #[test]
+            fn $name() {
+                let wire = json!(42);
+                let value: $ty = serde_json::from_value(wire.clone()).unwrap();
+                assert!(matches!(value, $variant(42)));
+                assert_eq!(serde_json::to_value(value).unwrap(), wire);
+            }
+        };
+    }
+
+    unknown_code_round_trip_test!(permission_preserves_unknown_codes, Permission, Permission::Unknown);
+    unknown_code_round_trip_test!(slot_type_preserves_unknown_codes, SlotType, SlotType::Unknown);
+    unknown_code_round_trip_test!(
+        client_status_preserves_unknown_codes,
+        ClientStatus,
+        ClientStatus::Unknown
+    );
+
+    #[test]
+    fn bounced_death_link_round_trips_through_a_real_payload() {
+        let death_link = DeathLink::new("Alice", Some("fell".to_string()), 123.0);
+        let bounce = death_link.clone().into_bounce();
+
+        // This is the actual wire shape of a `Bounced` packet: `data` is the
+        // arbitrary payload the sender put into the original `Bounce.data`.
+        let wire = json!({
+            "cmd": "Bounced",
+            "games": null,
+            "slots": null,
+            "tags": ["DeathLink"],
+            "data": bounce.data,
+        });
+
+        let message: ServerMessage = serde_json::from_value(wire).unwrap();
+        let ServerMessage::Bounced(bounced) = message else {
+            panic!("expected Bounced");
+        };
+
+        assert_eq!(bounced.as_death_link(), Some(death_link));
+    }
+
+    #[test]
+    fn bounced_as_death_link_ignores_untagged_bounces() {
+        let bounced = Bounced {
+            games: None,
+            slots: None,
+            tags: Some(vec!["Other".to_string()]),
+            data: json!({"time": 1.0, "source": "Alice"}),
+        };
+
+        assert_eq!(bounced.as_death_link(), None);
+    }
+
+    #[test]
+    fn print_json_render_resolves_ids_per_part_and_team() {
+        let mut package = DataPackageObject {
+            games: HashMap::new(),
+        };
+        package.games.insert(
+            "Clique".to_string(),
+            GameData {
+                item_name_to_id: HashMap::from([("Big Sword".to_string(), 1)]),
+                location_name_to_id: HashMap::new(),
+            },
+        );
+
+        let mut slot_info = HashMap::new();
+        slot_info.insert(
+            "2".to_string(),
+            NetworkSlot {
+                name: "P2".to_string(),
+                game: "Clique".to_string(),
+                r#type: SlotType::Player,
+                group_members: vec![],
+            },
+        );
+
+        let players = vec![
+            NetworkPlayer {
+                team: 0,
+                slot: 1,
+                alias: "Alice".to_string(),
+                name: "alice".to_string(),
+            },
+            NetworkPlayer {
+                team: 1,
+                slot: 1,
+                alias: "AliceOnOtherTeam".to_string(),
+                name: "alice2".to_string(),
+            },
+        ];
+
+        let ctx = PrintJsonContext {
+            team: 0,
+            slot_info: &slot_info,
+            package: &package,
+            players: &players,
+        };
+
+        let parts = vec![
+            JSONMessagePart {
+                r#type: Some("player_id".to_string()),
+                text: Some("1".to_string()),
+                color: None,
+                flags: None,
+                player: None,
+            },
+            JSONMessagePart {
+                r#type: Some("item_id".to_string()),
+                text: Some("1".to_string()),
+                color: None,
+                flags: None,
+                player: Some(2),
+            },
+        ];
+
+        let print_json = PrintJSON {
+            data: parts,
+            r#type: Some(PrintJsonType::ItemSend),
+            receiving: None,
+            item: None,
+            found: None,
+            countdown: None,
+        };
+
+        assert_eq!(print_json.render(&ctx), "AliceBig Sword");
+    }
+
+    #[test]
+    fn json_message_part_render_wraps_known_color_in_ansi_codes() {
+        let package = DataPackageObject {
+            games: HashMap::new(),
+        };
+        let slot_info = HashMap::new();
+        let players = vec![];
+
+        let ctx = PrintJsonContext {
+            team: 0,
+            slot_info: &slot_info,
+            package: &package,
+            players: &players,
+        };
+
+        let part = JSONMessagePart {
+            r#type: None,
+            text: Some("hinted!".to_string()),
+            color: Some("red_bg".to_string()),
+            flags: None,
+            player: None,
+        };
+
+        assert_eq!(part.render(&ctx), "\x1b[41mhinted!\x1b[0m");
+    }
+
+    #[test]
+    fn stale_data_package_request_requests_nothing_when_fully_cached() {
+        let mut store = InMemoryDataPackageStore::default();
+        store.write(
+            "Clique",
+            "abc".to_string(),
+            GameData {
+                item_name_to_id: HashMap::new(),
+                location_name_to_id: HashMap::new(),
+            },
+        );
+
+        let mut checksums = HashMap::new();
+        checksums.insert("Clique".to_string(), "abc".to_string());
+
+        let request = stale_data_package_request(&checksums, &store);
+        assert_eq!(request.games, Some(vec![]));
+    }
+
+    #[test]
+    fn stale_data_package_request_requests_changed_and_missing_games() {
+        let mut store = InMemoryDataPackageStore::default();
+        store.write(
+            "Clique",
+            "stale".to_string(),
+            GameData {
+                item_name_to_id: HashMap::new(),
+                location_name_to_id: HashMap::new(),
+            },
+        );
+
+        let mut checksums = HashMap::new();
+        checksums.insert("Clique".to_string(), "fresh".to_string());
+        checksums.insert("Slay the Spire".to_string(), "new".to_string());
+
+        let request = stale_data_package_request(&checksums, &store);
+        let mut games = request.games.unwrap();
+        games.sort();
+        assert_eq!(
+            games,
+            vec!["Clique".to_string(), "Slay the Spire".to_string()]
+        );
+    }
+
+    #[test]
+    fn data_storage_client_tracks_subscriptions_and_reconciles_set_reply() {
+        let client = DataStorageClient::new();
+        let notify = client.subscribe(vec!["counter".to_string()]);
+        assert_eq!(notify.keys, vec!["counter".to_string()]);
+        assert!(client.subscribed_keys().contains("counter"));
+        assert_eq!(client.resubscribe().keys, vec!["counter".to_string()]);
+
+        let change = client.reconcile_set_reply(SetReply {
+            key: "counter".to_string(),
+            value: json!(2),
+            original_value: json!(1),
+        });
+
+        assert_eq!(change.value, json!(2));
+        assert_eq!(client.read_cached("counter"), Some(json!(2)));
+    }
 }